@@ -0,0 +1,30 @@
+//! Zip archive of a request's outputs for `zip_output`.
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Store each file in `paths` in a new zip at `zip_path`, under its base
+/// name. Images are already compressed, so entries are stored as-is.
+pub fn write_zip<P: AsRef<Path>>(paths: &[P], zip_path: &Path) -> Result<()> {
+    let file = File::create(zip_path)
+        .with_context(|| format!("Failed to create {}", zip_path.display()))?;
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for path in paths {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .with_context(|| format!("Output has no usable file name: {}", path.display()))?;
+        zip.start_file(name, options)
+            .with_context(|| format!("Failed to add {} to the zip", name))?;
+        let mut input = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        std::io::copy(&mut input, &mut zip).with_context(|| format!("Failed to add {} to the zip", name))?;
+    }
+    zip.finish()?;
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! `--benchmark`: time a fixed resize + blur + save workload on a synthetic
+//! screenshot, so deployments can compare hardware and configurations
+//! without supplying any input files. Also compares `mosaic_block_size`
+//! with the equivalent full-image `pixelate` region.
+
+use crate::{mosaic, pixelate, process_image, BlurSpec, PixelateRegion, ProcessRequest};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Runs timed by `--benchmark`.
+pub const ITERATIONS: usize = 100;
+
+/// Size of the synthetic input, a full-HD screenshot.
+const WIDTH: u32 = 1920;
+const HEIGHT: u32 = 1080;
+
+const BLUR_SIGMA: f32 = 2.0;
+
+const MOSAIC_BLOCK_SIZE: u32 = 16;
+
+/// Printed as JSON on stdout
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub iterations: usize,
+    pub width: u32,
+    pub height: u32,
+    pub operations: String,
+    pub total_secs: f64,
+    pub images_per_second: f64,
+    pub mean_latency_ms: f64,
+    pub p99_latency_ms: f64,
+    /// Mean time to mosaic the input in memory with `mosaic_block_size`...
+    pub mosaic_mean_ms: f64,
+    /// ...and with a `pixelate` region covering it, for the same result
+    pub pixelate_mean_ms: f64,
+}
+
+/// Process the synthetic image `iterations` times, each a complete request
+/// from decoding the input to saving a PNG under the system temp directory
+/// (point `TMPDIR` at a tmpfs to keep the disk out of the numbers)
+pub fn run(iterations: usize) -> Result<Report> {
+    run_sized(iterations, WIDTH, HEIGHT)
+}
+
+/// `run` on a `width` x `height` input, resized to two thirds of that
+pub fn run_sized(iterations: usize, width: u32, height: u32) -> Result<Report> {
+    let dir = std::env::temp_dir().join(format!("rust_worker_benchmark_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let result = run_in(&dir, iterations.max(1), width, height);
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+fn run_in(dir: &std::path::Path, iterations: usize, width: u32, height: u32) -> Result<Report> {
+    let input_path = dir.join("input.png");
+    // Flat panels with gradients and some detail, roughly like a desktop
+    let input = image::RgbaImage::from_fn(width, height, |x, y| {
+        let panel = (x / 240 + y / 270) % 3;
+        let detail = ((x * 7 + y * 13) % 23) as u8;
+        image::Rgba([(x / 8) as u8 ^ detail, (y / 5) as u8, 60 + panel as u8 * 70, 255])
+    });
+    input.save(&input_path).context("Failed to write the benchmark input")?;
+
+    let resize = ((width * 2 / 3).max(1), (height * 2 / 3).max(1));
+    let request = ProcessRequest {
+        path: Some(input_path.to_string_lossy().into_owned()),
+        out_path: Some(dir.join("output.png").to_string_lossy().into_owned()),
+        resize: Some(resize),
+        blur_sigma: Some(BlurSpec::Isotropic(BLUR_SIGMA)),
+        ..Default::default()
+    };
+    let mut latencies = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let started = Instant::now();
+        process_image(&request)?;
+        latencies.push(started.elapsed());
+    }
+
+    let total: Duration = latencies.iter().sum();
+    latencies.sort();
+    let p99 = latencies[(iterations * 99).div_ceil(100) - 1];
+
+    // Only the pixel work is timed, not copying the input
+    let region = PixelateRegion { x: 0, y: 0, w: width, h: height, block_size: MOSAIC_BLOCK_SIZE };
+    let mean_ms = |run: &dyn Fn(&mut image::RgbaImage) -> Result<()>| -> Result<f64> {
+        let mut total = Duration::ZERO;
+        for _ in 0..iterations {
+            let mut img = input.clone();
+            let started = Instant::now();
+            run(&mut img)?;
+            total += started.elapsed();
+        }
+        Ok(total.as_secs_f64() * 1000.0 / iterations as f64)
+    };
+    let mosaic_mean_ms = mean_ms(&|img| {
+        mosaic(img, MOSAIC_BLOCK_SIZE);
+        Ok(())
+    })?;
+    let pixelate_mean_ms = mean_ms(&|img| pixelate(img, &region))?;
+    Ok(Report {
+        iterations,
+        width,
+        height,
+        operations: format!("resize {}x{}, blur sigma {}, save png", resize.0, resize.1, BLUR_SIGMA),
+        total_secs: total.as_secs_f64(),
+        images_per_second: iterations as f64 / total.as_secs_f64(),
+        mean_latency_ms: total.as_secs_f64() * 1000.0 / iterations as f64,
+        p99_latency_ms: p99.as_secs_f64() * 1000.0,
+        mosaic_mean_ms,
+        pixelate_mean_ms,
+    })
+}
@@ -0,0 +1,3 @@
+fn main() {
+    rust_worker::run();
+}
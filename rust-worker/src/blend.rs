@@ -0,0 +1,54 @@
+//! Photoshop-style blend modes for `blend`: each color channel of the base
+//! image is combined with the same channel of a second image.
+
+use image::RgbaImage;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Multiply,
+    Screen,
+    Overlay,
+    Difference,
+}
+
+impl Mode {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "multiply" => Ok(Mode::Multiply),
+            "screen" => Ok(Mode::Screen),
+            "overlay" => Ok(Mode::Overlay),
+            "difference" => Ok(Mode::Difference),
+            other => Err(format!(
+                "invalid blend mode '{}': expected \"multiply\", \"screen\", \"overlay\" or \"difference\"",
+                other
+            )),
+        }
+    }
+
+    /// Blend one channel value of the base with the top image's
+    pub fn channel(self, base: u8, top: u8) -> u8 {
+        let (a, b) = (f32::from(base) / 255.0, f32::from(top) / 255.0);
+        let blended = match self {
+            Mode::Multiply => a * b,
+            Mode::Screen => 1.0 - (1.0 - a) * (1.0 - b),
+            // Multiply the shadows and screen the highlights of the base
+            Mode::Overlay if a < 0.5 => 2.0 * a * b,
+            Mode::Overlay => 1.0 - 2.0 * (1.0 - a) * (1.0 - b),
+            Mode::Difference => (a - b).abs(),
+        };
+        (blended * 255.0).round().clamp(0.0, 255.0) as u8
+    }
+}
+
+/// Blend `top` (the same size as `base`) into `base`, mixing the blended
+/// color in by `alpha` times the top pixel's own alpha. The base alpha is
+/// kept.
+pub fn blend(base: &mut RgbaImage, top: &RgbaImage, mode: Mode, alpha: f32) {
+    for (pixel, top) in base.pixels_mut().zip(top.pixels()) {
+        let weight = alpha * f32::from(top[3]) / 255.0;
+        for (value, &other) in pixel.0[..3].iter_mut().zip(&top.0[..3]) {
+            let blended = f32::from(mode.channel(*value, other));
+            *value = (f32::from(*value) + (blended - f32::from(*value)) * weight).round() as u8;
+        }
+    }
+}
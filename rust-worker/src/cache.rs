@@ -0,0 +1,61 @@
+//! `cache_check`: skip a request whose output is newer than its input and
+//! was written for identical parameters, as recorded in a `.cache` sidecar
+//! next to the output.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+
+/// Contents of the sidecar, as JSON
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// `fingerprint` of the request that wrote the output
+    pub request_sha256: String,
+    /// Input size in bytes when it was processed
+    pub input_size: u64,
+    /// Output dimensions, reported again on a cache hit
+    pub width: u32,
+    pub height: u32,
+}
+
+/// SHA-256 over the worker version, `params`' debug representation and the
+/// size and modification time of each of `files` (the LUTs, overlays and
+/// other images the request reads besides its input), so any change to one
+/// of them misses the cache. Callers clear `out_path` first.
+pub fn fingerprint(params: &impl Debug, files: &[&str]) -> String {
+    let stamps: Vec<_> = files
+        .iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(path).ok();
+            (path, metadata.as_ref().map(|m| (m.len(), m.modified().ok())))
+        })
+        .collect();
+    let digest = Sha256::digest(format!("{} {:?} {:?}", env!("CARGO_PKG_VERSION"), params, stamps));
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// `out.png` -> `out.png.cache`
+pub fn sidecar_path(out_path: &Path) -> PathBuf {
+    let mut name = out_path.as_os_str().to_owned();
+    name.push(".cache");
+    PathBuf::from(name)
+}
+
+/// The recorded entry, if `out_path` is not older than `in_path` (equal
+/// times count as fresh, as in `make`, since coarse timestamps often tie),
+/// the input still has its recorded size and the sidecar was written for
+/// `request_sha256`
+pub fn lookup(in_path: &Path, out_path: &Path, request_sha256: &str) -> Option<CacheEntry> {
+    let input = std::fs::metadata(in_path).ok()?;
+    let output = std::fs::metadata(out_path).ok()?;
+    if output.modified().ok()? < input.modified().ok()? {
+        return None;
+    }
+    let entry: CacheEntry = serde_json::from_slice(&std::fs::read(sidecar_path(out_path)).ok()?).ok()?;
+    (entry.request_sha256 == request_sha256 && entry.input_size == input.len()).then_some(entry)
+}
+
+pub fn store(out_path: &Path, entry: &CacheEntry) -> std::io::Result<()> {
+    std::fs::write(sidecar_path(out_path), serde_json::to_vec(entry)?)
+}
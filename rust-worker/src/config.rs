@@ -0,0 +1,51 @@
+//! Request defaults loaded from a `--config` TOML file. Config keys use the
+//! same names as request fields; any field present in a request replaces the
+//! config value as a whole, and fields in neither fall back to the built-in
+//! behaviour.
+
+use crate::ProcessRequest;
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+#[derive(Debug, Default, Clone)]
+pub struct RequestDefaults {
+    fields: Map<String, Value>,
+}
+
+impl RequestDefaults {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let fields: Map<String, Value> = toml::from_str(&text)
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+        // Catch type errors now rather than on the first request
+        serde_json::from_value::<ProcessRequest>(Value::Object(fields.clone()))
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+        Ok(RequestDefaults { fields })
+    }
+
+    /// Fill in missing fields of a request object
+    pub fn apply(&self, request: &mut Value) {
+        if let Value::Object(request) = request {
+            for (key, value) in &self.fields {
+                request.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+    }
+
+    /// Apply the defaults to a single request, or to every job of a batch
+    pub fn apply_to_input(&self, input: &mut Value) {
+        match input.get_mut("jobs").and_then(Value::as_array_mut) {
+            Some(jobs) => jobs.iter_mut().for_each(|job| self.apply(job)),
+            None => self.apply(input),
+        }
+    }
+
+    /// Parse a single request, filling in the defaults
+    pub fn parse_request(&self, json: &[u8]) -> serde_json::Result<ProcessRequest> {
+        let mut value: Value = serde_json::from_slice(json)?;
+        self.apply(&mut value);
+        serde_json::from_value(value)
+    }
+}
@@ -0,0 +1,30 @@
+//! Visual diff between two screenshots of the same size.
+
+use image::{Rgba, RgbaImage};
+
+/// Multiplier applied to the largest channel difference so that small
+/// changes are still visible.
+const DIFF_GAIN: u32 = 4;
+
+/// Dimmest red used for a changed pixel, however small the change.
+const MIN_HIGHLIGHT: u32 = 96;
+
+/// Pixels where `a` and `b` differ, drawn in red over a dimmed grayscale
+/// copy of `a`, and the number of differing pixels. Both images must have
+/// the same dimensions.
+pub fn diff(a: &RgbaImage, b: &RgbaImage) -> (RgbaImage, u64) {
+    let mut changed = 0;
+    let out = RgbaImage::from_fn(a.width(), a.height(), |x, y| {
+        let (pa, pb) = (a.get_pixel(x, y), b.get_pixel(x, y));
+        let delta = pa.0.iter().zip(pb.0).map(|(&p, q)| p.abs_diff(q)).max().unwrap_or(0);
+        if delta == 0 {
+            let luma = image::Pixel::to_luma(pa).0[0] / 3;
+            Rgba([luma, luma, luma, 255])
+        } else {
+            changed += 1;
+            let red = (u32::from(delta) * DIFF_GAIN).clamp(MIN_HIGHLIGHT, 255) as u8;
+            Rgba([red, 0, 0, 255])
+        }
+    });
+    (out, changed)
+}
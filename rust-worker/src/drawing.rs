@@ -0,0 +1,145 @@
+//! Line drawing for `draw_lines`: Cohen-Sutherland clipping followed by a
+//! Bresenham walk that stamps a round pen at every point. Also the evenly
+//! spaced lines of `grid_overlay`.
+
+use image::{Pixel, Rgba, RgbaImage};
+
+const INSIDE: u8 = 0;
+const LEFT: u8 = 1;
+const RIGHT: u8 = 2;
+const TOP: u8 = 4;
+const BOTTOM: u8 = 8;
+
+type Point = (f64, f64);
+
+/// Draw a line from `start` to `end`, alpha-blending `color` over every pixel
+/// within `thickness / 2` of it, so widths are odd (an even `thickness` is
+/// drawn one pixel wider). Each pixel is blended once, however many pen
+/// stamps overlap it, and the parts off the image are clipped away.
+pub fn line(img: &mut RgbaImage, start: (i32, i32), end: (i32, i32), color: [u8; 4], thickness: u32) {
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 {
+        return;
+    }
+    let radius = i64::from(thickness / 2);
+    // Points just off the image can still reach it with a thick pen
+    let bounds = (
+        -radius as f64,
+        -radius as f64,
+        (i64::from(width) - 1 + radius) as f64,
+        (i64::from(height) - 1 + radius) as f64,
+    );
+    let as_point = |(x, y): (i32, i32)| (f64::from(x), f64::from(y));
+    let Some((start, end)) = clip(as_point(start), as_point(end), bounds) else {
+        return;
+    };
+
+    let mut covered = vec![false; width as usize * height as usize];
+    let mut stamp = |cx: i64, cy: i64| {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let (x, y) = (cx + dx, cy + dy);
+                if dx * dx + dy * dy <= radius * radius
+                    && (0..i64::from(width)).contains(&x)
+                    && (0..i64::from(height)).contains(&y)
+                {
+                    covered[y as usize * width as usize + x as usize] = true;
+                }
+            }
+        }
+    };
+    bresenham(start, end, &mut stamp);
+
+    let color = Rgba(color);
+    for (i, _) in covered.iter().enumerate().filter(|(_, &c)| c) {
+        let (x, y) = ((i % width as usize) as u32, (i / width as usize) as u32);
+        img.get_pixel_mut(x, y).blend(&color);
+    }
+}
+
+/// Region code of a point relative to `(x_min, y_min, x_max, y_max)`
+fn outcode((x, y): Point, (x_min, y_min, x_max, y_max): (f64, f64, f64, f64)) -> u8 {
+    let mut code = INSIDE;
+    if x < x_min {
+        code |= LEFT;
+    } else if x > x_max {
+        code |= RIGHT;
+    }
+    if y < y_min {
+        code |= TOP;
+    } else if y > y_max {
+        code |= BOTTOM;
+    }
+    code
+}
+
+/// Cohen-Sutherland: the part of the segment inside `bounds`, if any
+fn clip(mut p0: Point, mut p1: Point, bounds: (f64, f64, f64, f64)) -> Option<(Point, Point)> {
+    let (x_min, y_min, x_max, y_max) = bounds;
+    let (mut code0, mut code1) = (outcode(p0, bounds), outcode(p1, bounds));
+    loop {
+        if code0 | code1 == INSIDE {
+            return Some((p0, p1));
+        }
+        if code0 & code1 != INSIDE {
+            return None;
+        }
+        // Move the endpoint that is outside onto the edge it lies beyond.
+        // Its coordinate differs from the other endpoint's on that axis, so
+        // the divisions are safe.
+        let outside = if code0 != INSIDE { code0 } else { code1 };
+        let ((x0, y0), (x1, y1)) = (p0, p1);
+        let moved = if outside & TOP != 0 {
+            (x0 + (x1 - x0) * (y_min - y0) / (y1 - y0), y_min)
+        } else if outside & BOTTOM != 0 {
+            (x0 + (x1 - x0) * (y_max - y0) / (y1 - y0), y_max)
+        } else if outside & RIGHT != 0 {
+            (x_max, y0 + (y1 - y0) * (x_max - x0) / (x1 - x0))
+        } else {
+            (x_min, y0 + (y1 - y0) * (x_min - x0) / (x1 - x0))
+        };
+        if outside == code0 {
+            p0 = moved;
+            code0 = outcode(p0, bounds);
+        } else {
+            p1 = moved;
+            code1 = outcode(p1, bounds);
+        }
+    }
+}
+
+/// Visit every pixel of the line between the rounded endpoints
+fn bresenham(start: Point, end: Point, visit: &mut impl FnMut(i64, i64)) {
+    let (mut x, mut y) = (start.0.round() as i64, start.1.round() as i64);
+    let (x1, y1) = (end.0.round() as i64, end.1.round() as i64);
+    let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+    let (step_x, step_y) = (if x < x1 { 1 } else { -1 }, if y < y1 { 1 } else { -1 });
+    let mut error = dx + dy;
+    loop {
+        visit(x, y);
+        if x == x1 && y == y1 {
+            break;
+        }
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// Alpha-blend `color` over every pixel whose column is within `thickness`
+/// after a multiple of `cell_width`, or whose row is within `thickness` after
+/// a multiple of `cell_height`. Crossings are blended once.
+pub fn grid(img: &mut RgbaImage, cell_width: u32, cell_height: u32, color: [u8; 4], thickness: u32) {
+    let color = Rgba(color);
+    for (x, y, pixel) in img.enumerate_pixels_mut() {
+        if x % cell_width < thickness || y % cell_height < thickness {
+            pixel.blend(&color);
+        }
+    }
+}
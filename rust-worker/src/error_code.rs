@@ -0,0 +1,90 @@
+//! Machine-readable `error_code` values for failed responses, so callers can
+//! tell failures apart without parsing `msg`.
+
+use serde::Serialize;
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// Malformed JSON, unknown arguments or conflicting request fields
+    InvalidRequest,
+    /// The input file (or the `--request-file`) does not exist
+    IoInputNotFound,
+    /// Any other failure to read or write a file, socket or URL
+    IoError,
+    /// An input or output format that is not recognized or not built in
+    UnsupportedFormat,
+    /// The input is in a known format but could not be decoded
+    DecodeFailed,
+    /// An operation parameter is out of range or does not fit the image
+    TransformParamInvalid,
+    /// A size limit such as `max_input_bytes` was exceeded
+    LimitExceeded,
+    /// `timeout_secs` elapsed
+    Timeout,
+    /// The server is shutting down
+    Unavailable,
+    Unknown,
+}
+
+impl ErrorCode {
+    /// An error with this code that displays as `message`
+    pub fn msg(self, message: impl Into<String>) -> CodedError {
+        CodedError { code: self, message: message.into() }
+    }
+}
+
+/// An error tagged with its `ErrorCode`, used directly or as context
+#[derive(Debug)]
+pub struct CodedError {
+    code: ErrorCode,
+    message: String,
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+/// The code of the outermost `CodedError` in `err`, or else one inferred from
+/// the `image` or I/O error underneath it
+pub fn classify(err: &anyhow::Error) -> ErrorCode {
+    if let Some(coded) = err.downcast_ref::<CodedError>() {
+        return coded.code;
+    }
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<image::ImageError>() {
+            return match e {
+                image::ImageError::Unsupported(_) => ErrorCode::UnsupportedFormat,
+                image::ImageError::Decoding(_) => ErrorCode::DecodeFailed,
+                image::ImageError::Limits(_) => ErrorCode::LimitExceeded,
+                image::ImageError::Parameter(_) => ErrorCode::TransformParamInvalid,
+                image::ImageError::IoError(_) => ErrorCode::IoError,
+                image::ImageError::Encoding(_) => ErrorCode::Unknown,
+            };
+        }
+        if cause.is::<io::Error>() {
+            return ErrorCode::IoError;
+        }
+        if cause.is::<serde_json::Error>() {
+            return ErrorCode::InvalidRequest;
+        }
+    }
+    ErrorCode::Unknown
+}
+
+/// Whether `err` comes from a missing file
+pub fn is_not_found(err: &anyhow::Error) -> bool {
+    // `image` wraps I/O errors without exposing them as a source
+    err.chain()
+        .filter_map(|cause| match cause.downcast_ref::<image::ImageError>() {
+            Some(image::ImageError::IoError(e)) => Some(e),
+            _ => cause.downcast_ref::<io::Error>(),
+        })
+        .any(|e| e.kind() == io::ErrorKind::NotFound)
+}
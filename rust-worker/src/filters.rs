@@ -0,0 +1,57 @@
+//! Edge-preserving smoothing for `bilateral_filter`: every pixel becomes a
+//! weighted average of its neighbourhood, where neighbours lose weight both
+//! with distance and with color difference, so noise within flat areas is
+//! averaged away while pixels across an edge barely contribute.
+
+use image::{Rgba, RgbaImage};
+
+/// Largest color distance: the sum of the absolute RGB differences
+const MAX_COLOR_DISTANCE: usize = 3 * 255;
+
+/// Bilateral filter over a circular neighbourhood `diameter` pixels across,
+/// with gaussian falloffs of `sigma_space` pixels and `sigma_color` (on the
+/// sum of absolute RGB differences). A `diameter` of 0 or less is derived
+/// from `sigma_space`, as in OpenCV. Alpha is averaged with the same
+/// weights, and pixels beyond the image repeat its edge.
+pub fn bilateral(img: &RgbaImage, diameter: i32, sigma_color: f64, sigma_space: f64) -> RgbaImage {
+    let (width, height) = img.dimensions();
+    let radius = i64::from(radius(diameter, sigma_space));
+
+    let space_coefficient = -0.5 / (sigma_space * sigma_space);
+    let offsets: Vec<(i64, i64, f64)> = (-radius..=radius)
+        .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+        .filter(|&(dx, dy)| dx * dx + dy * dy <= radius * radius)
+        .map(|(dx, dy)| (dx, dy, (((dx * dx + dy * dy) as f64) * space_coefficient).exp()))
+        .collect();
+    let color_coefficient = -0.5 / (sigma_color * sigma_color);
+    let color_weights: Vec<f64> = (0..=MAX_COLOR_DISTANCE)
+        .map(|distance| ((distance * distance) as f64 * color_coefficient).exp())
+        .collect();
+
+    let clamp = |value: i64, len: u32| value.clamp(0, i64::from(len) - 1) as u32;
+    RgbaImage::from_fn(width, height, |x, y| {
+        let center = img.get_pixel(x, y);
+        let mut sums = [0.0f64; 4];
+        let mut total = 0.0;
+        for &(dx, dy, space_weight) in &offsets {
+            let neighbour = img.get_pixel(clamp(i64::from(x) + dx, width), clamp(i64::from(y) + dy, height));
+            let distance: usize = (0..3).map(|c| usize::from(center[c].abs_diff(neighbour[c]))).sum();
+            let weight = space_weight * color_weights[distance];
+            for (sum, &value) in sums.iter_mut().zip(&neighbour.0) {
+                *sum += weight * f64::from(value);
+            }
+            total += weight;
+        }
+        // The center always has weight 1, so `total` is never 0
+        Rgba(sums.map(|sum| (sum / total).round().clamp(0.0, 255.0) as u8))
+    })
+}
+
+/// How many pixels `bilateral` looks in each direction
+pub fn radius(diameter: i32, sigma_space: f64) -> u32 {
+    if diameter > 0 {
+        diameter as u32 / 2
+    } else {
+        (sigma_space * 1.5).round() as u32
+    }
+}
@@ -0,0 +1,64 @@
+//! Perceptual hashes for spotting duplicate screenshots: `compute_ahash` and
+//! `compute_phash`. Similar images get hashes that differ in few bits, so
+//! callers compare them by Hamming distance.
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+use std::f64::consts::PI;
+
+/// Side of the grayscale copy the DCT of `perceptual` runs on.
+const DCT_SIDE: u32 = 32;
+
+/// Side of the block of lowest frequencies `perceptual` keeps.
+const HASH_SIDE: usize = 8;
+
+/// Average hash: bit `i` is set when pixel `i` (row-major) of an 8x8
+/// grayscale copy is brighter than the copy's mean.
+pub fn average(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(8, 8, FilterType::Triangle).into_luma8();
+    let values: Vec<f64> = small.pixels().map(|p| f64::from(p[0])).collect();
+    bits_above(&values, values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// DCT hash: bit `i` is set when coefficient `i` (row-major) of the 8x8
+/// lowest frequencies of a 32x32 grayscale copy's DCT is above their median.
+/// The DC term, which only tracks overall brightness, is left out of the
+/// median.
+pub fn perceptual(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(DCT_SIDE, DCT_SIDE, FilterType::Triangle).into_luma8();
+    let n = DCT_SIDE as usize;
+    let pixels: Vec<f64> = small.pixels().map(|p| f64::from(p[0])).collect();
+    // cos((2x + 1) u pi / 2n), shared by rows and columns
+    let basis: Vec<f64> = (0..HASH_SIDE)
+        .flat_map(|u| (0..n).map(move |x| ((2 * x + 1) as f64 * u as f64 * PI / (2 * n) as f64).cos()))
+        .collect();
+
+    let mut coefficients = Vec::with_capacity(HASH_SIDE * HASH_SIDE);
+    for v in 0..HASH_SIDE {
+        for u in 0..HASH_SIDE {
+            let mut sum = 0.0;
+            for y in 0..n {
+                let row: f64 = (0..n).map(|x| pixels[y * n + x] * basis[u * n + x]).sum();
+                sum += row * basis[v * n + y];
+            }
+            coefficients.push(sum);
+        }
+    }
+
+    let mut sorted = coefficients[1..].to_vec();
+    sorted.sort_by(f64::total_cmp);
+    bits_above(&coefficients, sorted[sorted.len() / 2])
+}
+
+/// 16 lowercase hex digits, as hashes are reported
+pub fn to_hex(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+fn bits_above(values: &[f64], threshold: f64) -> u64 {
+    values
+        .iter()
+        .enumerate()
+        .filter(|(_, &value)| value > threshold)
+        .fold(0, |hash, (i, _)| hash | 1 << i)
+}
@@ -0,0 +1,166 @@
+//! 1D color lookup tables loaded from `.cube` or CSV files.
+
+use crate::error_code::ErrorCode;
+use anyhow::{Context, Result};
+use image::RgbaImage;
+use std::path::Path;
+
+/// Number of entries a LUT must have, one per 8-bit input value.
+pub const LUT_SIZE: usize = 256;
+
+/// Per-channel output values (0.0-255.0) for each input level
+pub struct Lut {
+    entries: Vec<[f32; 3]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    Nearest,
+    Linear,
+}
+
+impl Interpolation {
+    pub fn from_name(name: Option<&str>) -> Result<Self, String> {
+        match name {
+            None | Some("linear") => Ok(Interpolation::Linear),
+            Some("nearest") => Ok(Interpolation::Nearest),
+            Some(other) => Err(format!("invalid LUT interpolation '{}': expected \"nearest\" or \"linear\"", other)),
+        }
+    }
+}
+
+impl Lut {
+    /// Load a `.cube` file (by extension) or a CSV with one `value` or
+    /// `r,g,b` line (0-255) per entry. `#` starts a comment in both.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read LUT: {}", path.display()))?;
+        let is_cube = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("cube"));
+        let lut = if is_cube { parse_cube(&text) } else { parse_csv(&text) };
+        lut.with_context(|| ErrorCode::TransformParamInvalid.msg(format!("Malformed LUT: {}", path.display())))
+    }
+
+    /// Map the color channels of every pixel; alpha is untouched.
+    pub fn apply(&self, img: &mut RgbaImage, interpolation: Interpolation) {
+        // Inputs are 8-bit, so precompute the result for every level
+        let table: Vec<[u8; 3]> = (0..LUT_SIZE)
+            .map(|level| self.sample(level as f32, interpolation).map(|v| v.round().clamp(0.0, 255.0) as u8))
+            .collect();
+        for pixel in img.pixels_mut() {
+            for channel in 0..3 {
+                pixel[channel] = table[usize::from(pixel[channel])][channel];
+            }
+        }
+    }
+
+    /// Output at input level `level` (0.0-255.0), between entries if needed
+    fn sample(&self, level: f32, interpolation: Interpolation) -> [f32; 3] {
+        let position = level / 255.0 * (self.entries.len() - 1) as f32;
+        match interpolation {
+            Interpolation::Nearest => self.entries[position.round() as usize],
+            Interpolation::Linear => {
+                let low = position.floor() as usize;
+                let high = (low + 1).min(self.entries.len() - 1);
+                let t = position - low as f32;
+                let (a, b) = (self.entries[low], self.entries[high]);
+                [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * t)
+            }
+        }
+    }
+}
+
+fn check_size(entries: Vec<[f32; 3]>) -> Result<Lut> {
+    if entries.len() != LUT_SIZE {
+        anyhow::bail!("expected {} entries, found {}", LUT_SIZE, entries.len());
+    }
+    Ok(Lut { entries })
+}
+
+/// Lines with the comment stripped, skipping blank ones, with 1-based numbers
+fn content_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.split('#').next().unwrap_or("").trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+fn parse_numbers<'a>(fields: impl Iterator<Item = &'a str>, line_no: usize) -> Result<Vec<f32>> {
+    fields
+        .map(|field| {
+            field
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .with_context(|| format!("line {}: invalid number '{}'", line_no, field.trim()))
+        })
+        .collect()
+}
+
+fn parse_csv(text: &str) -> Result<Lut> {
+    let mut entries = Vec::new();
+    for (line_no, line) in content_lines(text) {
+        let values = parse_numbers(line.split(','), line_no)?;
+        let entry = match values[..] {
+            [v] => [v; 3],
+            [r, g, b] => [r, g, b],
+            _ => anyhow::bail!("line {}: expected 1 or 3 values, found {}", line_no, values.len()),
+        };
+        if entry.iter().any(|v| !(0.0..=255.0).contains(v)) {
+            anyhow::bail!("line {}: values must be between 0 and 255", line_no);
+        }
+        entries.push(entry);
+    }
+    check_size(entries)
+}
+
+/// Adobe/Resolve `.cube` format; only 1D tables are supported.
+fn parse_cube(text: &str) -> Result<Lut> {
+    let mut entries = Vec::new();
+    let mut declared = None;
+    let (mut min, mut max) = ([0.0f32; 3], [1.0f32; 3]);
+    for (line_no, line) in content_lines(text) {
+        let mut fields = line.split_whitespace();
+        let keyword = fields.next().unwrap_or("");
+        match keyword {
+            "TITLE" => {}
+            "LUT_1D_SIZE" => {
+                let size = fields.next().and_then(|s| s.parse::<usize>().ok());
+                declared = Some(size.with_context(|| format!("line {}: invalid LUT_1D_SIZE", line_no))?);
+            }
+            "LUT_3D_SIZE" => anyhow::bail!("3D LUTs are not supported"),
+            "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                let values = parse_numbers(fields, line_no)?;
+                let [r, g, b] = values[..] else {
+                    anyhow::bail!("line {}: {} needs 3 values", line_no, keyword);
+                };
+                if keyword == "DOMAIN_MIN" {
+                    min = [r, g, b];
+                } else {
+                    max = [r, g, b];
+                }
+            }
+            _ => {
+                let values = parse_numbers(line.split_whitespace(), line_no)?;
+                let [r, g, b] = values[..] else {
+                    anyhow::bail!("line {}: expected 3 values, found {}", line_no, values.len());
+                };
+                entries.push([r, g, b]);
+            }
+        }
+    }
+    if declared.is_some_and(|size| size != entries.len()) {
+        anyhow::bail!("LUT_1D_SIZE {} does not match the {} entries", declared.unwrap_or(0), entries.len());
+    }
+    if (0..3).any(|c| max[c] <= min[c]) {
+        anyhow::bail!("DOMAIN_MAX must be greater than DOMAIN_MIN");
+    }
+
+    // Normalize outputs from the domain range to 0-255
+    for entry in &mut entries {
+        for c in 0..3 {
+            entry[c] = (entry[c] - min[c]) / (max[c] - min[c]) * 255.0;
+        }
+    }
+    check_size(entries)
+}
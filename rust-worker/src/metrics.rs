@@ -0,0 +1,47 @@
+//! Prometheus metrics for `GET /metrics` in server mode.
+
+use prometheus::{Encoder, Histogram, HistogramOpts, IntCounterVec, Opts, Registry, TextEncoder};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    duration: Histogram,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(|| {
+    let requests = IntCounterVec::new(
+        Opts::new("worker_requests_total", "Processed requests by outcome"),
+        &["status"],
+    )
+    .expect("valid counter");
+    let duration = Histogram::with_opts(HistogramOpts::new(
+        "worker_processing_duration_seconds",
+        "Time spent processing a request",
+    ))
+    .expect("valid histogram");
+    let registry = Registry::new();
+    registry.register(Box::new(requests.clone())).expect("unique metric");
+    registry.register(Box::new(duration.clone())).expect("unique metric");
+    Metrics { registry, requests, duration }
+});
+
+/// Count one processed request and its duration
+pub fn record(ok: bool, elapsed: Duration) {
+    let status = if ok { "ok" } else { "error" };
+    METRICS.requests.with_label_values(&[status]).inc();
+    METRICS.duration.observe(elapsed.as_secs_f64());
+}
+
+/// Every metric in the Prometheus text exposition format
+pub fn render() -> String {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&METRICS.registry.gather(), &mut buffer)
+        .expect("text encoding cannot fail");
+    String::from_utf8(buffer).expect("text format is UTF-8")
+}
+
+/// Content type of `render`'s output
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
@@ -0,0 +1,71 @@
+//! Multi-page TIFFs: assembled from the outputs of a batch, and single pages
+//! read back for `tiff_page`.
+
+use crate::error_code::ErrorCode;
+use anyhow::{Context, Result};
+use image::{DynamicImage, ImageBuffer};
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek};
+use std::path::Path;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::encoder::{colortype, TiffEncoder};
+use tiff::ColorType;
+
+/// Write every image in `pages` as one RGBA page of a TIFF at `out_path`, in
+/// order, and return the number of pages written.
+pub fn write_tiff<P: AsRef<Path>>(pages: &[P], out_path: &Path) -> Result<usize> {
+    let file = File::create(out_path)
+        .with_context(|| format!("Failed to create {}", out_path.display()))?;
+    let mut encoder = TiffEncoder::new(BufWriter::new(file))?;
+    for page in pages {
+        let page = page.as_ref();
+        let img = image::open(page)
+            .with_context(|| format!("Failed to open page: {}", page.display()))?
+            .into_rgba8();
+        encoder
+            .write_image::<colortype::RGBA8>(img.width(), img.height(), img.as_raw())
+            .with_context(|| format!("Failed to encode page: {}", page.display()))?;
+    }
+    Ok(pages.len())
+}
+
+/// Decode page `page` (0-based) of the TIFF in `reader`. 8- and 16-bit gray
+/// and RGB pages, with or without alpha, keep their depth.
+pub fn read_tiff_page<R: Read + Seek>(reader: R, page: usize) -> Result<DynamicImage> {
+    let mut decoder = Decoder::new(reader).context(ErrorCode::DecodeFailed.msg("Failed to read TIFF"))?;
+    let mut pages = 1;
+    while pages <= page && decoder.more_images() {
+        decoder
+            .next_image()
+            .with_context(|| ErrorCode::DecodeFailed.msg(format!("Failed to read TIFF page {}", pages)))?;
+        pages += 1;
+    }
+    if pages <= page {
+        // Walked off the end, so `pages` is the total
+        anyhow::bail!(ErrorCode::TransformParamInvalid.msg(format!(
+            "tiff_page {} is out of range; the TIFF has {} page{}",
+            page,
+            pages,
+            if pages == 1 { "" } else { "s" }
+        )));
+    }
+
+    let decode_error = || ErrorCode::DecodeFailed.msg(format!("Failed to decode TIFF page {}", page));
+    let (width, height) = decoder.dimensions().with_context(decode_error)?;
+    let color_type = decoder.colortype().with_context(decode_error)?;
+    let img = match (color_type, decoder.read_image().with_context(decode_error)?) {
+        (ColorType::Gray(8), DecodingResult::U8(buf)) => ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageLuma8),
+        (ColorType::GrayA(8), DecodingResult::U8(buf)) => ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageLumaA8),
+        (ColorType::RGB(8), DecodingResult::U8(buf)) => ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageRgb8),
+        (ColorType::RGBA(8), DecodingResult::U8(buf)) => ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageRgba8),
+        (ColorType::Gray(16), DecodingResult::U16(buf)) => ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageLuma16),
+        (ColorType::GrayA(16), DecodingResult::U16(buf)) => ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageLumaA16),
+        (ColorType::RGB(16), DecodingResult::U16(buf)) => ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageRgb16),
+        (ColorType::RGBA(16), DecodingResult::U16(buf)) => ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageRgba16),
+        (color_type, _) => anyhow::bail!(ErrorCode::UnsupportedFormat.msg(format!(
+            "TIFF page {} has unsupported color type {:?}",
+            page, color_type
+        ))),
+    };
+    img.with_context(decode_error)
+}
@@ -0,0 +1,161 @@
+//! Median-cut color quantization: the dominant colors for `extract_palette`,
+//! and the reduced palette `quantize_colors` maps an image onto.
+
+use image::imageops::{self, ColorMap};
+use image::{Rgba, RgbaImage};
+use std::collections::BinaryHeap;
+
+/// Largest side of the copy the palette is computed from.
+const MAX_SAMPLE_SIDE: u32 = 256;
+
+/// Up to `count` dominant colors of `img`, most common first. Fully
+/// transparent pixels are ignored, so an image made only of them has an
+/// empty palette, and an image with fewer distinct colors than `count`
+/// returns each of them once.
+pub fn extract(img: &RgbaImage, count: usize) -> Vec<[u8; 3]> {
+    let (width, height) = img.dimensions();
+    let scale = f64::from(MAX_SAMPLE_SIDE) / f64::from(width.max(height).max(1));
+    let sample;
+    let img = if scale < 1.0 {
+        let side = |n: u32| ((f64::from(n) * scale).round() as u32).max(1);
+        sample = imageops::thumbnail(img, side(width), side(height));
+        &sample
+    } else {
+        img
+    };
+
+    let pixels: Vec<[u8; 3]> = img.pixels().filter(|p| p[3] > 0).map(|p| [p[0], p[1], p[2]]).collect();
+    let mut boxes = median_cut(&pixels, count);
+    boxes.sort_by_key(|b| std::cmp::Reverse(b.len()));
+    boxes.iter().map(|b| average(&pixels, b)).collect()
+}
+
+/// Replace every pixel's color with the average of its median-cut box, for
+/// at most `count` colors in all. With `dither`, pixels instead take the
+/// nearest of those averages in Floyd-Steinberg order, diffusing the error
+/// so gradients don't band. Alpha is left as it is.
+pub fn quantize(img: &mut RgbaImage, count: usize, dither: bool) {
+    let pixels: Vec<[u8; 3]> = img.pixels().map(|p| [p[0], p[1], p[2]]).collect();
+    let boxes = median_cut(&pixels, count);
+    if dither {
+        let palette = Palette(boxes.iter().map(|members| average(&pixels, members)).collect());
+        imageops::dither(img, &palette);
+        return;
+    }
+    let mut colors = vec![[0; 3]; pixels.len()];
+    for members in boxes {
+        let color = average(&pixels, &members);
+        for i in members {
+            colors[i as usize] = color;
+        }
+    }
+    for (pixel, color) in img.pixels_mut().zip(colors) {
+        pixel.0[..3].copy_from_slice(&color);
+    }
+}
+
+/// A box of colors, ordered by its widest channel spread so the heap pops
+/// the box most worth splitting
+struct ColorBox {
+    range: u8,
+    channel: usize,
+    members: Vec<u32>,
+}
+
+impl ColorBox {
+    fn new(pixels: &[[u8; 3]], members: Vec<u32>) -> Self {
+        let (channel, range) = (0..3)
+            .map(|channel| {
+                let (min, max) = members.iter().fold((u8::MAX, u8::MIN), |(min, max), &i| {
+                    let value = pixels[i as usize][channel];
+                    (min.min(value), max.max(value))
+                });
+                (channel, max - min)
+            })
+            .max_by_key(|&(_, range)| range)
+            .unwrap_or((0, 0));
+        ColorBox { range, channel, members }
+    }
+}
+
+impl PartialEq for ColorBox {
+    fn eq(&self, other: &Self) -> bool {
+        self.range == other.range
+    }
+}
+
+impl Eq for ColorBox {}
+
+impl PartialOrd for ColorBox {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ColorBox {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.range.cmp(&other.range)
+    }
+}
+
+/// Split the pixels into at most `count` boxes of indices into `pixels`,
+/// halving the box with the widest channel spread at its median each time.
+/// Stops early once every box holds a single color.
+fn median_cut(pixels: &[[u8; 3]], count: usize) -> Vec<Vec<u32>> {
+    if pixels.is_empty() || count == 0 {
+        return Vec::new();
+    }
+    let mut heap = BinaryHeap::new();
+    heap.push(ColorBox::new(pixels, (0..pixels.len() as u32).collect()));
+
+    while heap.len() < count {
+        let Some(widest) = heap.peek() else { break };
+        if widest.range == 0 {
+            break;
+        }
+        let ColorBox { channel, mut members, .. } = heap.pop().expect("peeked");
+        members.sort_unstable_by_key(|&i| pixels[i as usize][channel]);
+        // Keep equal values together so both halves stay non-empty
+        let value = |i: u32| pixels[i as usize][channel];
+        let median = value(members[members.len() / 2]);
+        let mut split = members.partition_point(|&i| value(i) < median);
+        if split == 0 {
+            split = members.partition_point(|&i| value(i) <= median);
+        }
+        let upper = members.split_off(split);
+        heap.push(ColorBox::new(pixels, members));
+        heap.push(ColorBox::new(pixels, upper));
+    }
+    heap.into_iter().map(|b| b.members).collect()
+}
+
+/// Box averages to map dithered pixels onto
+struct Palette(Vec<[u8; 3]>);
+
+impl ColorMap for Palette {
+    type Color = Rgba<u8>;
+
+    fn index_of(&self, color: &Rgba<u8>) -> usize {
+        let distance = |entry: &[u8; 3]| -> u32 {
+            entry.iter().zip(&color.0).map(|(&a, &b)| u32::from(a.abs_diff(b)).pow(2)).sum()
+        };
+        (0..self.0.len()).min_by_key(|&i| distance(&self.0[i])).unwrap_or(0)
+    }
+
+    fn map_color(&self, color: &mut Rgba<u8>) {
+        if let Some(entry) = self.0.get(self.index_of(color)) {
+            color.0[..3].copy_from_slice(entry);
+        }
+    }
+}
+
+fn average(pixels: &[[u8; 3]], members: &[u32]) -> [u8; 3] {
+    let mut sums = [0u64; 3];
+    for &i in members {
+        for (sum, &value) in sums.iter_mut().zip(&pixels[i as usize]) {
+            *sum += u64::from(value);
+        }
+    }
+    let n = members.len() as u64;
+    sums.map(|sum| ((sum + n / 2) / n) as u8)
+}
@@ -0,0 +1,95 @@
+//! Image metrics computed over the luma channel: the full-reference PSNR and
+//! SSIM, and the histogram entropy behind `compute_complexity`.
+
+use image::GrayImage;
+
+/// PSNR reported for identical images, where the true value is infinite.
+pub const MAX_PSNR: f64 = 100.0;
+
+/// Side length of the square windows SSIM is averaged over.
+const SSIM_WINDOW: u32 = 8;
+
+/// Peak signal-to-noise ratio in decibels. Both images must have the same
+/// dimensions.
+pub fn psnr(a: &GrayImage, b: &GrayImage) -> f64 {
+    let sum_sq: f64 = a
+        .as_raw()
+        .iter()
+        .zip(b.as_raw())
+        .map(|(&x, &y)| (f64::from(x) - f64::from(y)).powi(2))
+        .sum();
+    let mse = sum_sq / a.as_raw().len().max(1) as f64;
+    if mse == 0.0 {
+        return MAX_PSNR;
+    }
+    (10.0 * (255.0 * 255.0 / mse).log10()).min(MAX_PSNR)
+}
+
+/// Mean structural similarity over non-overlapping 8x8 windows (smaller at
+/// the right and bottom edges). Both images must have the same dimensions.
+pub fn ssim(a: &GrayImage, b: &GrayImage) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let (width, height) = a.dimensions();
+    let mut total = 0.0;
+    let mut windows = 0u32;
+
+    for wy in (0..height).step_by(SSIM_WINDOW as usize) {
+        for wx in (0..width).step_by(SSIM_WINDOW as usize) {
+            let w = SSIM_WINDOW.min(width - wx);
+            let h = SSIM_WINDOW.min(height - wy);
+            let n = f64::from(w * h);
+
+            let (mut sum_a, mut sum_b) = (0.0, 0.0);
+            let (mut sum_aa, mut sum_bb, mut sum_ab) = (0.0, 0.0, 0.0);
+            for y in wy..wy + h {
+                for x in wx..wx + w {
+                    let pa = f64::from(a.get_pixel(x, y)[0]);
+                    let pb = f64::from(b.get_pixel(x, y)[0]);
+                    sum_a += pa;
+                    sum_b += pb;
+                    sum_aa += pa * pa;
+                    sum_bb += pb * pb;
+                    sum_ab += pa * pb;
+                }
+            }
+
+            let mean_a = sum_a / n;
+            let mean_b = sum_b / n;
+            let var_a = sum_aa / n - mean_a * mean_a;
+            let var_b = sum_bb / n - mean_b * mean_b;
+            let covar = sum_ab / n - mean_a * mean_b;
+
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covar + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+
+    if windows == 0 {
+        1.0
+    } else {
+        total / f64::from(windows)
+    }
+}
+
+/// Shannon entropy of the gray-level histogram, `-sum(p * log2(p))` over the
+/// levels present, divided by its maximum of 8 bits: 0.0 for a single level,
+/// 1.0 when all 256 are equally common. An empty image scores 0.0.
+pub fn entropy(img: &GrayImage) -> f64 {
+    let mut histogram = [0u64; 256];
+    for &value in img.as_raw() {
+        histogram[usize::from(value)] += 1;
+    }
+    let total = img.as_raw().len() as f64;
+    let bits: f64 = histogram
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum();
+    bits / 8.0
+}
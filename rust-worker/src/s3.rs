@@ -0,0 +1,228 @@
+//! `s3://bucket/key` inputs and outputs on S3-compatible storage, signed with
+//! AWS Signature Version 4. Credentials and region come from the standard
+//! `AWS_*` environment variables; set `AWS_ENDPOINT_URL` to use another
+//! S3-compatible service (addressed path-style).
+
+use crate::error_code::ErrorCode;
+use anyhow::{Context, Result};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCHEME: &str = "s3://";
+
+/// Lifetime of the presigned URLs inputs are downloaded through.
+pub const PRESIGN_EXPIRES_SECS: u64 = 300;
+
+/// Region used when neither `AWS_REGION` nor `AWS_DEFAULT_REGION` is set.
+const DEFAULT_REGION: &str = "us-east-1";
+
+pub fn is_s3_url(path: &str) -> bool {
+    path.starts_with(SCHEME)
+}
+
+/// Where and as whom to talk to S3
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    /// Base URL of an S3-compatible service; AWS itself when `None`
+    pub endpoint: Option<String>,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl S3Config {
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        Ok(S3Config {
+            endpoint: var("AWS_ENDPOINT_URL_S3").or_else(|| var("AWS_ENDPOINT_URL")),
+            region: var("AWS_REGION")
+                .or_else(|| var("AWS_DEFAULT_REGION"))
+                .unwrap_or_else(|| DEFAULT_REGION.to_string()),
+            access_key_id: var("AWS_ACCESS_KEY_ID").with_context(|| ErrorCode::InvalidRequest.msg("AWS_ACCESS_KEY_ID is not set"))?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY").with_context(|| ErrorCode::InvalidRequest.msg("AWS_SECRET_ACCESS_KEY is not set"))?,
+            session_token: var("AWS_SESSION_TOKEN"),
+        })
+    }
+
+    /// Host, request URL and signed (encoded) path of an object
+    fn locate(&self, bucket: &str, key: &str) -> (String, String, String) {
+        let key = uri_encode(key, false);
+        match &self.endpoint {
+            Some(endpoint) => {
+                let base = endpoint.trim_end_matches('/');
+                let host = base.split_once("://").map_or(base, |(_, rest)| rest);
+                let path = format!("/{}/{}", uri_encode(bucket, true), key);
+                (host.to_string(), format!("{}{}", base, path), path)
+            }
+            None => {
+                // us-east-1 is also served from the original global endpoint
+                let host = match self.region.as_str() {
+                    "us-east-1" => format!("{}.s3.amazonaws.com", bucket),
+                    region => format!("{}.s3.{}.amazonaws.com", bucket, region),
+                };
+                let path = format!("/{}", key);
+                (host.clone(), format!("https://{}{}", host, path), path)
+            }
+        }
+    }
+
+    /// `YYYYMMDD/region/s3/aws4_request`
+    fn scope(&self, date: &str) -> String {
+        format!("{}/{}/s3/aws4_request", date, self.region)
+    }
+
+    /// Sign `canonical_request` as of `timestamp` (`YYYYMMDDTHHMMSSZ`)
+    fn sign(&self, timestamp: &str, canonical_request: &str) -> String {
+        let date = &timestamp[..8];
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            self.scope(date),
+            hex(&Sha256::digest(canonical_request))
+        );
+        let key = [date, self.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(format!("AWS4{}", self.secret_access_key).into_bytes(), |key, part| hmac(&key, part));
+        hex(&hmac(&key, &string_to_sign))
+    }
+}
+
+/// Split `s3://bucket/key` into its bucket and key
+fn parse(url: &str) -> Result<(&str, &str)> {
+    url.strip_prefix(SCHEME)
+        .and_then(|rest| rest.split_once('/'))
+        .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+        .with_context(|| ErrorCode::InvalidRequest.msg(format!("Invalid S3 URL (expected s3://bucket/key): {}", url)))
+}
+
+/// Presigned GET URL for `url`, valid for `expires_secs` from `now` (Unix seconds)
+pub fn presign_get(config: &S3Config, url: &str, now: u64, expires_secs: u64) -> Result<String> {
+    let (bucket, key) = parse(url)?;
+    let (host, object_url, path) = config.locate(bucket, key);
+    let timestamp = amz_timestamp(now);
+
+    let mut query = vec![
+        ("X-Amz-Algorithm".to_string(), "AWS4-HMAC-SHA256".to_string()),
+        (
+            "X-Amz-Credential".to_string(),
+            format!("{}/{}", config.access_key_id, config.scope(&timestamp[..8])),
+        ),
+        ("X-Amz-Date".to_string(), timestamp.clone()),
+        ("X-Amz-Expires".to_string(), expires_secs.to_string()),
+        ("X-Amz-SignedHeaders".to_string(), "host".to_string()),
+    ];
+    if let Some(token) = &config.session_token {
+        query.push(("X-Amz-Security-Token".to_string(), token.clone()));
+    }
+    query.sort();
+    let query = query
+        .iter()
+        .map(|(k, v)| format!("{}={}", uri_encode(k, true), uri_encode(v, true)))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let canonical_request = format!("GET\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD", path, query, host);
+    let signature = config.sign(&timestamp, &canonical_request);
+    Ok(format!("{}?{}&X-Amz-Signature={}", object_url, query, signature))
+}
+
+/// Upload `body` to `url` as of `now` (Unix seconds)
+pub fn put_object(config: &S3Config, url: &str, body: &[u8], content_type: &str, now: u64) -> Result<()> {
+    let (bucket, key) = parse(url)?;
+    let (host, object_url, path) = config.locate(bucket, key);
+    let timestamp = amz_timestamp(now);
+    let payload_hash = hex(&Sha256::digest(body));
+
+    let mut headers = vec![
+        ("host", host),
+        ("x-amz-content-sha256", payload_hash.clone()),
+        ("x-amz-date", timestamp.clone()),
+    ];
+    if let Some(token) = &config.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+    let canonical_request = format!(
+        "PUT\n{}\n\n{}\n{}\n{}",
+        path, canonical_headers, signed_headers, payload_hash
+    );
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        config.access_key_id,
+        config.scope(&timestamp[..8]),
+        signed_headers,
+        config.sign(&timestamp, &canonical_request)
+    );
+
+    // ureq sets Host itself, from the same URL
+    let mut request = ureq::put(&object_url)
+        .set("Authorization", &authorization)
+        .set("Content-Type", content_type);
+    for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+        request = request.set(name, value);
+    }
+    match request.send_bytes(body) {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(code, response)) => {
+            let status = response.status_text().to_string();
+            // S3 explains failures in an XML body
+            let detail = response.into_string().unwrap_or_default();
+            anyhow::bail!(ErrorCode::IoError.msg(format!(
+                "Upload to {} failed with HTTP {} {}: {}",
+                url, code, status, detail.trim()
+            )))
+        }
+        Err(e) => Err(e).with_context(|| format!("Failed to upload to {}", url)),
+    }
+}
+
+/// Current time in Unix seconds
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// `YYYYMMDDTHHMMSSZ` for a Unix timestamp
+fn amz_timestamp(secs: u64) -> String {
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year, month, day, rem / 3_600, rem % 3_600 / 60, rem % 60
+    )
+}
+
+/// Percent-encode everything but unreserved characters, keeping `/` in
+/// object keys
+fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(byte as char),
+            b'/' if !encode_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,109 @@
+//! Content-aware downsizing for `seam_carve`: repeatedly remove the
+//! connected path of pixels with the least gradient energy, so flat areas
+//! shrink while edges and text keep their shape.
+
+use image::{Rgba, RgbaImage};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Shrink `img` to `width` x `height` (each no larger than the image's) by
+/// removing vertical seams for the width, then horizontal ones for the height.
+/// `None` if `cancel` was set before the last seam.
+pub fn carve(img: &RgbaImage, width: u32, height: u32, cancel: &AtomicBool) -> Option<RgbaImage> {
+    let mut grid = Grid {
+        width: img.width() as usize,
+        height: img.height() as usize,
+        pixels: img.pixels().copied().collect(),
+    };
+    let cancelled = || cancel.load(Ordering::SeqCst);
+    while grid.width > width as usize {
+        if cancelled() {
+            return None;
+        }
+        grid.remove_seam();
+    }
+    // Horizontal seams are vertical ones of the transposed image
+    grid = grid.transposed();
+    while grid.width > height as usize {
+        if cancelled() {
+            return None;
+        }
+        grid.remove_seam();
+    }
+    let grid = grid.transposed();
+
+    Some(RgbaImage::from_fn(grid.width as u32, grid.height as u32, |x, y| {
+        grid.pixels[y as usize * grid.width + x as usize]
+    }))
+}
+
+/// Row-major pixels that shrink one column at a time
+struct Grid {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgba<u8>>,
+}
+
+impl Grid {
+    fn transposed(self) -> Grid {
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for x in 0..self.width {
+            for y in 0..self.height {
+                pixels.push(self.pixels[y * self.width + x]);
+            }
+        }
+        Grid { width: self.height, height: self.width, pixels }
+    }
+
+    /// Gradient magnitude of the luma, with edges repeating outward
+    fn energy(&self) -> Vec<u32> {
+        let (w, h) = (self.width, self.height);
+        let luma: Vec<i32> = self
+            .pixels
+            .iter()
+            .map(|p| (299 * i32::from(p[0]) + 587 * i32::from(p[1]) + 114 * i32::from(p[2])) / 1000)
+            .collect();
+        let at = |x: usize, y: usize| luma[y * w + x];
+        let mut energy = Vec::with_capacity(w * h);
+        for y in 0..h {
+            for x in 0..w {
+                let dx = at((x + 1).min(w - 1), y) - at(x.saturating_sub(1), y);
+                let dy = at(x, (y + 1).min(h - 1)) - at(x, y.saturating_sub(1));
+                energy.push(dx.unsigned_abs() + dy.unsigned_abs());
+            }
+        }
+        energy
+    }
+
+    /// Remove the top-to-bottom, 8-connected path with the lowest total energy
+    fn remove_seam(&mut self) {
+        let (w, h) = (self.width, self.height);
+        let mut cost = self.energy();
+        for y in 1..h {
+            for x in 0..w {
+                let above = &cost[(y - 1) * w + x.saturating_sub(1)..(y - 1) * w + (x + 2).min(w)];
+                let cheapest = above.iter().copied().min().unwrap_or(0);
+                cost[y * w + x] += cheapest;
+            }
+        }
+
+        // Walk back up from the cheapest end, staying connected
+        let mut seam = vec![0; h];
+        let last = &cost[(h - 1) * w..];
+        seam[h - 1] = (0..w).min_by_key(|&x| last[x]).unwrap_or(0);
+        for y in (0..h - 1).rev() {
+            let below = seam[y + 1];
+            seam[y] = (below.saturating_sub(1)..(below + 2).min(w))
+                .min_by_key(|&x| cost[y * w + x])
+                .unwrap_or(below);
+        }
+
+        let mut pixels = Vec::with_capacity((w - 1) * h);
+        for (y, &skip) in seam.iter().enumerate() {
+            let row = &self.pixels[y * w..(y + 1) * w];
+            pixels.extend_from_slice(&row[..skip]);
+            pixels.extend_from_slice(&row[skip + 1..]);
+        }
+        self.pixels = pixels;
+        self.width -= 1;
+    }
+}
@@ -0,0 +1,145 @@
+//! Minimal HTTP/1.1 front end so a long-lived worker can skip per-image
+//! process startup. Requests are handled one at a time. On SIGTERM or
+//! SIGINT the server stops taking work, lets the in-flight request finish
+//! and exits.
+
+use crate::config::RequestDefaults;
+use crate::error_code::ErrorCode;
+use crate::metrics;
+use crate::{run_request, ProcessRequest, ProcessResponse};
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// How often the server checks for a shutdown signal.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Longest wait for the in-flight request once a shutdown signal arrives.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Listen on `addr` and answer `POST` requests carrying a `ProcessRequest`
+/// JSON body with a `ProcessResponse`. `GET /health` runs a `noop` request
+/// and `GET /metrics` reports Prometheus metrics. Returns once a shutdown
+/// signal has been handled.
+pub fn run(addr: &str, defaults: &RequestDefaults) -> Result<()> {
+    let terminate = shutdown_flag()?;
+    let server = Server::http(addr).map_err(|e| anyhow::anyhow!("Failed to bind {}: {}", addr, e))?;
+    let defaults = Arc::new(defaults.clone());
+    eprintln!("Listening on http://{}", server.server_addr());
+
+    while !terminate.load(Ordering::SeqCst) {
+        let request = match server.recv_timeout(POLL_INTERVAL) {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!("Failed to receive request: {}", e);
+                continue;
+            }
+        };
+
+        // Processed on its own thread so a signal is noticed mid-request
+        let (done, finished) = mpsc::channel();
+        let defaults = Arc::clone(&defaults);
+        thread::spawn(move || {
+            if let Err(e) = handle(request, &defaults) {
+                tracing::warn!("Failed to send response: {}", e);
+            }
+            let _ = done.send(());
+        });
+        loop {
+            match finished.recv_timeout(POLL_INTERVAL) {
+                Err(RecvTimeoutError::Timeout) if terminate.load(Ordering::SeqCst) => {
+                    drain(&server, &finished);
+                    return Ok(());
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                // A panicking handler drops the sender without sending
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+    tracing::info!("Shutting down");
+    Ok(())
+}
+
+/// Set once SIGTERM or SIGINT arrives; never set where signals are unsupported.
+fn shutdown_flag() -> Result<Arc<AtomicBool>> {
+    let terminate = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT] {
+        use anyhow::Context;
+        signal_hook::flag::register(signal, Arc::clone(&terminate))
+            .context("Failed to install signal handler")?;
+    }
+    Ok(terminate)
+}
+
+/// Wait up to `DRAIN_TIMEOUT` for the in-flight request, turning away any
+/// request that arrives meanwhile.
+fn drain(server: &Server, finished: &Receiver<()>) {
+    tracing::info!("Shutting down after the in-flight request");
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    while finished.try_recv() == Err(TryRecvError::Empty) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            tracing::warn!("In-flight request still running after {:?}; exiting anyway", DRAIN_TIMEOUT);
+            return;
+        }
+        if let Ok(Some(request)) = server.recv_timeout(remaining.min(POLL_INTERVAL)) {
+            let response = ProcessResponse::failure(ErrorCode::Unavailable, "server shutting down".to_string());
+            if let Err(e) = request.respond(json_response(&response, 503)) {
+                tracing::warn!("Failed to send response: {}", e);
+            }
+        }
+    }
+}
+
+fn handle(mut request: Request, defaults: &RequestDefaults) -> std::io::Result<()> {
+    if *request.method() == Method::Get && request.url() == "/health" {
+        let response = run_request(&ProcessRequest { noop: Some(true), ..Default::default() });
+        return request.respond(json_response(&response, 200));
+    }
+    if *request.method() == Method::Get && request.url() == "/metrics" {
+        let content_type = Header::from_bytes("Content-Type", metrics::CONTENT_TYPE).unwrap();
+        return request.respond(Response::from_string(metrics::render()).with_header(content_type));
+    }
+    if *request.method() != Method::Post {
+        let response = ProcessResponse::failure(ErrorCode::InvalidRequest, "Only POST requests are supported".to_string());
+        return request.respond(json_response(&response, 405));
+    }
+
+    let mut body = String::new();
+    if let Err(e) = request.as_reader().read_to_string(&mut body) {
+        let response = ProcessResponse::failure(ErrorCode::IoError, format!("Failed to read request body: {}", e));
+        return request.respond(json_response(&response, 400));
+    }
+
+    let job = match defaults.parse_request(body.as_bytes()) {
+        Ok(job) => job,
+        Err(e) => {
+            let response = ProcessResponse::failure(ErrorCode::InvalidRequest, format!("Invalid request: {}", e));
+            return request.respond(json_response(&response, 400));
+        }
+    };
+
+    // Stdout belongs to the server process, not the client
+    if job.writes_to_stdout() {
+        let response = ProcessResponse::failure(ErrorCode::InvalidRequest, "out_path \"-\" is not supported in server mode".to_string());
+        return request.respond(json_response(&response, 400));
+    }
+
+    let response = run_request(&job);
+    let status = if response.ok { 200 } else { 500 };
+    request.respond(json_response(&response, status))
+}
+
+fn json_response(response: &ProcessResponse, status: u16) -> Response<std::io::Cursor<Vec<u8>>> {
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    Response::from_string(serde_json::to_string(response).unwrap())
+        .with_status_code(status)
+        .with_header(content_type)
+}
@@ -0,0 +1,36 @@
+//! Contact-sheet grid for `tile`, and tile offsets for `split_tiles`.
+
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, Rgba, RgbaImage};
+
+/// Place `images` left to right, top to bottom in a grid `columns` wide. Each
+/// image is scaled to fit its `cell_width` x `cell_height` cell, keeping its
+/// aspect ratio, and centered in it; cells are `gap` pixels apart and the
+/// rest of the canvas is `background`.
+pub fn grid(images: &[DynamicImage], columns: u32, cell_width: u32, cell_height: u32, gap: u32, background: [u8; 4]) -> RgbaImage {
+    let rows = (images.len() as u32).div_ceil(columns);
+    let span = |cells: u32, cell: u32| cells * cell + cells.saturating_sub(1) * gap;
+    let mut canvas = RgbaImage::from_pixel(span(columns, cell_width), span(rows, cell_height), Rgba(background));
+
+    for (i, img) in images.iter().enumerate() {
+        let (column, row) = (i as u32 % columns, i as u32 / columns);
+        let cell = img.resize(cell_width, cell_height, FilterType::Lanczos3).into_rgba8();
+        let x = column * (cell_width + gap) + (cell_width - cell.width()) / 2;
+        let y = row * (cell_height + gap) + (cell_height - cell.height()) / 2;
+        imageops::replace(&mut canvas, &cell, i64::from(x), i64::from(y));
+    }
+    canvas
+}
+
+/// Start offsets of `tile`-sized pieces covering `length`, each `overlap`
+/// pixels into the previous one. The last piece is moved back to end at
+/// `length` when the step doesn't divide it evenly. Requires
+/// `overlap < tile <= length`.
+pub fn offsets(length: u32, tile: u32, overlap: u32) -> Vec<u32> {
+    let last = length - tile;
+    let mut offsets: Vec<u32> = (0..=last).step_by((tile - overlap) as usize).collect();
+    if offsets.last() != Some(&last) {
+        offsets.push(last);
+    }
+    offsets
+}
@@ -0,0 +1,119 @@
+//! UNIX domain socket front end: each frame is a 4-byte big-endian length
+//! followed by that many bytes of UTF-8 JSON. Requests are answered with a
+//! `ProcessResponse` frame, one connection at a time, until SIGTERM. A
+//! connection waiting for its next frame is closed once SIGTERM arrives.
+
+use crate::config::RequestDefaults;
+use crate::error_code::ErrorCode;
+use crate::{run_request, ProcessResponse};
+use anyhow::{Context, Result};
+use std::io::{self, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Largest request frame accepted; anything bigger closes the connection.
+const MAX_FRAME_BYTES: u32 = 64 * 1024 * 1024;
+
+/// How often the accept loop and idle connections check for SIGTERM.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Listen on the socket at `path` until SIGTERM, then remove it.
+pub fn run(path: &Path, defaults: &RequestDefaults) -> Result<()> {
+    let terminate = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGTERM, Arc::clone(&terminate))
+        .context("Failed to install SIGTERM handler")?;
+
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Failed to bind {}", path.display()))?;
+    // Non-blocking so the loop can notice SIGTERM between connections
+    listener.set_nonblocking(true)?;
+    eprintln!("Listening on unix:{}", path.display());
+
+    while !terminate.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = handle(stream, defaults, &terminate) {
+                    tracing::warn!("Connection failed: {}", e);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => tracing::warn!("Failed to accept connection: {}", e),
+        }
+    }
+
+    std::fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    Ok(())
+}
+
+/// Answer frames on one connection until the client closes it or SIGTERM
+/// arrives between frames.
+fn handle(mut stream: UnixStream, defaults: &RequestDefaults, terminate: &AtomicBool) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    // Reads wake up regularly so a silent client cannot hold off shutdown
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    while let Some(frame) = read_frame(&mut stream, terminate)? {
+        let response = match defaults.parse_request(&frame) {
+            // Stdout belongs to the server process, not the client
+            Ok(job) if job.writes_to_stdout() => {
+                ProcessResponse::failure(ErrorCode::InvalidRequest, "out_path \"-\" is not supported in socket mode".to_string())
+            }
+            Ok(job) => run_request(&job),
+            Err(e) => ProcessResponse::failure(ErrorCode::InvalidRequest, format!("Invalid request: {}", e)),
+        };
+        write_frame(&mut stream, serde_json::to_string(&response)?.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Read one frame, or `None` on a clean end of stream or SIGTERM.
+fn read_frame(stream: &mut UnixStream, terminate: &AtomicBool) -> io::Result<Option<Vec<u8>>> {
+    let mut header = [0u8; 4];
+    if !read_full(stream, &mut header, terminate)? {
+        return Ok(None);
+    }
+    let len = u32::from_be_bytes(header);
+    if len > MAX_FRAME_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Frame of {} bytes exceeds the {} byte limit", len, MAX_FRAME_BYTES),
+        ));
+    }
+    let mut frame = vec![0; len as usize];
+    if !read_full(stream, &mut frame, terminate)? {
+        return Ok(None);
+    }
+    Ok(Some(frame))
+}
+
+/// Fill `buf`, riding out read timeouts. Returns `false` when the client
+/// closes the connection before sending anything, or when SIGTERM arrives
+/// during a timeout.
+fn read_full(stream: &mut UnixStream, buf: &mut [u8], terminate: &AtomicBool) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match stream.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted) => {
+                if terminate.load(Ordering::SeqCst) {
+                    return Ok(false);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+fn write_frame(stream: &mut UnixStream, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Response frame too large"))?;
+    stream.write_all(&len.to_be_bytes())?;
+    stream.write_all(payload)?;
+    stream.flush()
+}
@@ -0,0 +1,48 @@
+//! `video_frame_at_secs`: grab one frame of a video file as a PNG by running
+//! `ffmpeg`, which must be on `PATH`.
+
+use crate::error_code::ErrorCode;
+use anyhow::{Context, Result};
+use std::ffi::OsStr;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The frame shown `secs` into the video at `path`, encoded as PNG
+pub fn extract_frame(path: &Path, secs: f64) -> Result<Vec<u8>> {
+    extract_frame_with(OsStr::new("ffmpeg"), path, secs)
+}
+
+/// `extract_frame` through the `ffmpeg` executable `program`
+pub fn extract_frame_with(program: &OsStr, path: &Path, secs: f64) -> Result<Vec<u8>> {
+    // -ss before -i seeks on keyframes and decodes forward, which is fast
+    // and still lands on the exact frame
+    let output = Command::new(program)
+        .args(["-hide_banner", "-loglevel", "error", "-ss", &format!("{:.3}", secs), "-i"])
+        .arg(path)
+        .args(["-frames:v", "1", "-f", "image2pipe", "-c:v", "png", "-"])
+        .stdin(Stdio::null())
+        .output();
+    let output = match output {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => anyhow::bail!(ErrorCode::UnsupportedFormat.msg(
+            "video_frame_at_secs needs ffmpeg, which was not found on PATH"
+        )),
+        result => result.context("Failed to run ffmpeg")?,
+    };
+    if !output.status.success() {
+        anyhow::bail!(ErrorCode::DecodeFailed.msg(format!(
+            "ffmpeg could not read {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    if output.stdout.is_empty() {
+        // Seeking past the end succeeds but yields no frame
+        anyhow::bail!(ErrorCode::TransformParamInvalid.msg(format!(
+            "{} has no frame at {} seconds",
+            path.display(),
+            secs
+        )));
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,79 @@
+//! Text rendering for `text_watermark`, using a bundled font so no font
+//! files need to be installed.
+
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use anyhow::{Context, Result};
+use image::{Rgba, RgbaImage};
+
+/// DejaVu Sans Mono (Bitstream Vera license, see `assets/DejaVuSansMono-LICENSE.txt`).
+const FONT: &[u8] = include_bytes!("../assets/DejaVuSansMono.ttf");
+
+/// Largest `font_size` accepted, in pixels: each glyph is rasterized whole
+pub const MAX_FONT_SIZE: f32 = 1024.0;
+
+/// Draw `text` with its top-left corner at (`x`, `y`). Newlines start a new
+/// line; anything outside the image is clipped.
+pub fn draw_text(img: &mut RgbaImage, text: &str, x: i64, y: i64, font_size: f32, color: [u8; 4]) -> Result<()> {
+    let font = FontRef::try_from_slice(FONT).context("Bundled font is invalid")?;
+    let font = font.as_scaled(PxScale::from(font_size));
+    let line_height = font.height() + font.line_gap();
+
+    // Render into a layer covering the part of the text that lands on the
+    // image, so it can be composited in one go
+    let lines: Vec<&str> = text.lines().collect();
+    let text_width = lines
+        .iter()
+        .map(|line| line.chars().map(|c| font.h_advance(font.glyph_id(c))).sum::<f32>())
+        .fold(0.0, f32::max)
+        .ceil() as i64;
+    let text_height = (line_height * lines.len() as f32).ceil() as i64;
+    let (left, top) = (x.max(0), y.max(0));
+    let right = x.saturating_add(text_width).min(i64::from(img.width()));
+    let bottom = y.saturating_add(text_height).min(i64::from(img.height()));
+    if right <= left || bottom <= top {
+        return Ok(());
+    }
+    let (width, height) = (right - left, bottom - top);
+    let mut layer = RgbaImage::new(width as u32, height as u32);
+
+    for (row, line) in lines.iter().enumerate() {
+        let baseline = font.ascent() + line_height * row as f32;
+        let mut caret = 0.0;
+        let mut previous = None;
+        for c in line.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += font.kern(previous, id);
+            }
+            let glyph = id.with_scale_and_position(font.scale(), point(caret, baseline));
+            caret += font.h_advance(id);
+            previous = Some(id);
+
+            let Some(outline) = font.outline_glyph(glyph) else {
+                continue;
+            };
+            // Glyph origin in layer coordinates
+            let bounds = outline.px_bounds();
+            let (glyph_x, glyph_y) = (x + bounds.min.x as i64 - left, y + bounds.min.y as i64 - top);
+            let off_layer = glyph_x >= width || glyph_y >= height;
+            if off_layer || glyph_x + bounds.width() as i64 <= 0 || glyph_y + bounds.height() as i64 <= 0 {
+                continue;
+            }
+            outline.draw(|gx, gy, coverage| {
+                let px = glyph_x + i64::from(gx);
+                let py = glyph_y + i64::from(gy);
+                if px < 0 || py < 0 || px >= width || py >= height {
+                    return;
+                }
+                let alpha = (f32::from(color[3]) * coverage.clamp(0.0, 1.0)).round() as u8;
+                let pixel = layer.get_pixel_mut(px as u32, py as u32);
+                if alpha > pixel[3] {
+                    *pixel = Rgba([color[0], color[1], color[2], alpha]);
+                }
+            });
+        }
+    }
+
+    image::imageops::overlay(img, &layer, left, top);
+    Ok(())
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{run_worker, write_png};
+use serde_json::json;
+use tempfile::tempdir;
+
+#[test]
+fn batch_collects_all_results() {
+    let dir = tempdir().unwrap();
+    let good = dir.path().join("good.png");
+    write_png(&good);
+
+    let (success, reply) = run_worker(&json!({
+        "jobs": [
+            { "path": good, "resize": [2, 2] },
+            { "path": dir.path().join("missing.png") },
+            { "path": good, "out_path": dir.path().join("third.png") },
+        ]
+    }));
+
+    assert!(!success);
+    assert_eq!(reply["total"], 3);
+    assert_eq!(reply["succeeded"], 2);
+    let results = reply["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1]["ok"], false);
+    assert!(dir.path().join("third.png").exists());
+}
+
+#[test]
+fn batch_stops_on_first_error() {
+    let dir = tempdir().unwrap();
+    let good = dir.path().join("good.png");
+    write_png(&good);
+
+    let (success, reply) = run_worker(&json!({
+        "stop_on_error": true,
+        "jobs": [
+            { "path": good },
+            { "path": dir.path().join("missing.png") },
+            { "path": good, "out_path": dir.path().join("never.png") },
+        ]
+    }));
+
+    assert!(!success);
+    assert_eq!(reply["total"], 3);
+    assert_eq!(reply["succeeded"], 1);
+    assert_eq!(reply["results"].as_array().unwrap().len(), 2);
+    assert!(!dir.path().join("never.png").exists());
+}
+
+#[test]
+fn single_request_still_supported() {
+    let dir = tempdir().unwrap();
+    let good = dir.path().join("good.png");
+    write_png(&good);
+
+    let (success, reply) = run_worker(&json!({ "path": good }));
+
+    assert!(success);
+    assert_eq!(reply["ok"], true);
+    assert!(dir.path().join("good_processed.png").exists());
+}
+
+#[test]
+fn batch_writes_multipage_tiff() {
+    let dir = tempdir().unwrap();
+    let good = dir.path().join("good.png");
+    write_png(&good);
+    let tiff_path = dir.path().join("all.tiff");
+
+    let (success, reply) = run_worker(&json!({
+        "tiff_multipage_out": tiff_path,
+        "jobs": [
+            { "path": good, "out_path": dir.path().join("a.png") },
+            { "path": good, "out_path": dir.path().join("b.png"), "resize": [2, 3] },
+        ]
+    }));
+
+    assert!(success, "{}", reply);
+    assert_eq!(reply["tiff_pages"], 2);
+    let mut decoder = tiff::decoder::Decoder::new(std::fs::File::open(&tiff_path).unwrap()).unwrap();
+    assert_eq!(decoder.dimensions().unwrap(), (4, 4));
+    decoder.read_image().unwrap();
+    assert!(decoder.more_images());
+    decoder.next_image().unwrap();
+    assert_eq!(decoder.dimensions().unwrap(), (2, 3));
+    decoder.read_image().unwrap();
+    assert!(!decoder.more_images());
+
+    // Nothing is combined when a job fails
+    std::fs::remove_file(&tiff_path).unwrap();
+    let (success, reply) = run_worker(&json!({
+        "tiff_multipage_out": tiff_path,
+        "jobs": [
+            { "path": good, "out_path": dir.path().join("a.png") },
+            { "path": dir.path().join("missing.png") },
+        ]
+    }));
+    assert!(!success);
+    assert!(reply["tiff_error"].is_string());
+    assert!(!tiff_path.exists());
+}
+
+#[test]
+fn batch_parse_error_is_reported() {
+    let dir = tempdir().unwrap();
+    let good = dir.path().join("good.png");
+    write_png(&good);
+
+    let (success, reply) = run_worker(&json!({
+        "jobs": [
+            { "path": good },
+            { "path": good, "resize": "big" },
+        ]
+    }));
+
+    assert!(!success);
+    assert_eq!(reply["ok"], false);
+    assert_eq!(reply["error_code"], "INVALID_REQUEST");
+    let msg = reply["msg"].as_str().unwrap();
+    assert!(msg.starts_with("Invalid batch") && msg.contains("big"), "{}", reply);
+}
@@ -0,0 +1,234 @@
+mod common;
+
+use common::{run_worker_with_args, run_worker_with_env, write_png};
+use serde_json::json;
+use tempfile::tempdir;
+
+#[test]
+fn request_file_replaces_stdin() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.png");
+    let out = dir.path().join("from_file.png");
+    write_png(&input);
+
+    let request_file = dir.path().join("request.json");
+    std::fs::write(
+        &request_file,
+        json!({ "path": input, "out_path": out }).to_string(),
+    )
+    .unwrap();
+
+    // Whatever arrives on stdin is ignored
+    let (success, reply) = run_worker_with_args(
+        &["--request-file", request_file.to_str().unwrap()],
+        &json!({ "path": "ignored.png" }),
+    );
+
+    assert!(success);
+    assert_eq!(reply["ok"], true);
+    assert!(out.exists());
+}
+
+#[test]
+fn missing_request_file_is_reported() {
+    let dir = tempdir().unwrap();
+    let missing = dir.path().join("nope.json");
+
+    let (success, reply) =
+        run_worker_with_args(&["--request-file", missing.to_str().unwrap()], &json!({}));
+
+    assert!(!success);
+    assert_eq!(reply["ok"], false);
+    assert!(reply["msg"].as_str().unwrap().contains("request file"));
+    assert_eq!(reply["error_code"], "IO_INPUT_NOT_FOUND");
+}
+
+#[test]
+fn stdout_output_moves_response_to_stderr() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.png");
+    write_png(&input);
+
+    for (out_format, expected) in [(None, image::ImageFormat::Png), (Some("jpeg"), image::ImageFormat::Jpeg)] {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_rust_worker"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let request = json!({ "path": input, "out_path": "-", "out_format": out_format });
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(request.to_string().as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(image::guess_format(&output.stdout).unwrap(), expected);
+        let decoded = image::load_from_memory(&output.stdout).unwrap();
+        assert_eq!(decoded.width(), 4);
+
+        let reply: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+        assert_eq!(reply["ok"], true);
+        assert_eq!(reply["out_path"], "-");
+    }
+}
+
+#[test]
+fn panic_still_replies_with_json() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.png");
+    write_png(&input);
+
+    let (success, reply) = run_worker_with_env(
+        &[],
+        &[("RUST_WORKER_PANIC_PATH", input.as_os_str())],
+        &json!({ "path": input, "out_path": dir.path().join("out.png") }),
+    );
+
+    assert!(!success);
+    assert_eq!(reply["ok"], false);
+    assert_eq!(reply["error_code"], "UNKNOWN");
+    assert!(reply["msg"].as_str().unwrap().contains("Worker panicked"), "{}", reply);
+}
+
+#[test]
+fn config_file_supplies_defaults() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.png");
+    write_png(&input);
+    let config = dir.path().join("defaults.toml");
+    std::fs::write(&config, "resize = [2, 2]\ngrayscale = true\n").unwrap();
+    let config = config.to_str().unwrap();
+
+    let (success, reply) = run_worker_with_args(
+        &["--config", config],
+        &json!({ "path": input, "out_path": dir.path().join("a.png") }),
+    );
+    assert!(success, "{}", reply);
+    assert_eq!((reply["width"].clone(), reply["height"].clone()), (json!(2), json!(2)));
+
+    // Request fields win over the config
+    let (success, reply) = run_worker_with_args(
+        &["--config", config],
+        &json!({ "jobs": [{ "path": input, "out_path": dir.path().join("b.png"), "resize": [3, 1] }] }),
+    );
+    assert!(success, "{}", reply);
+    assert_eq!(reply["results"][0]["width"], 3);
+
+    std::fs::write(dir.path().join("bad.toml"), "resize = \"big\"\n").unwrap();
+    let (success, reply) = run_worker_with_args(
+        &["--config", dir.path().join("bad.toml").to_str().unwrap()],
+        &json!({ "path": input }),
+    );
+    assert!(!success);
+    assert!(reply["msg"].as_str().unwrap().contains("Invalid config file"), "{}", reply);
+}
+
+#[test]
+fn help_and_version_exit_without_reading_stdin() {
+    use std::process::{Command, Stdio};
+
+    for flag in ["--help", "-h"] {
+        // stdin is left open: the worker must not wait on it
+        let output = Command::new(env!("CARGO_BIN_EXE_rust_worker"))
+            .arg(flag)
+            .stdin(Stdio::piped())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let help = String::from_utf8(output.stdout).unwrap();
+        for option in ["--request-file", "--config", "--serve", "--unix-socket", "--stream", "--benchmark", "--version"] {
+            assert!(help.contains(option), "{} missing from help:\n{}", option, help);
+        }
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rust_worker"))
+        .arg("--version")
+        .stdin(Stdio::piped())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        format!("rust_worker {}", env!("CARGO_PKG_VERSION"))
+    );
+}
+
+#[test]
+fn stream_answers_each_line_as_it_arrives() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::{Command, Stdio};
+
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.png");
+    write_png(&input);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust_worker"))
+        .arg("--stream")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut replies = BufReader::new(child.stdout.take().unwrap()).lines();
+
+    // The first reply arrives while stdin is still open
+    let first = json!({ "path": input, "out_path": dir.path().join("a.png"), "resize": [2, 2] });
+    writeln!(stdin, "{}", first).unwrap();
+    let reply: serde_json::Value = serde_json::from_str(&replies.next().unwrap().unwrap()).unwrap();
+    assert_eq!(reply["ok"], true, "{}", reply);
+    assert_eq!(reply["width"], 2);
+
+    writeln!(stdin, "{}", json!({ "path": dir.path().join("missing.png") })).unwrap();
+    let reply: serde_json::Value = serde_json::from_str(&replies.next().unwrap().unwrap()).unwrap();
+    assert_eq!(reply["ok"], false);
+
+    drop(stdin);
+    assert!(replies.next().is_none());
+    assert!(child.wait().unwrap().success());
+    assert!(dir.path().join("a.png").exists());
+}
+
+#[test]
+fn stream_carries_on_after_a_panic() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let dir = tempdir().unwrap();
+    let panics = dir.path().join("panics.png");
+    let input = dir.path().join("in.png");
+    write_png(&panics);
+    write_png(&input);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust_worker"))
+        .arg("--stream")
+        .env("RUST_WORKER_PANIC_PATH", &panics)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    writeln!(stdin, "{}", json!({ "path": panics, "out_path": dir.path().join("a.png") })).unwrap();
+    writeln!(stdin, "{}", json!({ "path": input, "out_path": dir.path().join("b.png") })).unwrap();
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let replies: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(replies.len(), 2);
+    assert_eq!(replies[0]["error_code"], "UNKNOWN");
+    assert!(replies[0]["msg"].as_str().unwrap().contains("Worker panicked"), "{}", replies[0]);
+    assert_eq!(replies[1]["ok"], true, "{}", replies[1]);
+    assert!(dir.path().join("b.png").exists());
+}
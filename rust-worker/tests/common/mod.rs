@@ -0,0 +1,41 @@
+#![allow(dead_code)]
+
+use serde_json::Value;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Run the worker binary with `args` and `input` on stdin and parse its JSON reply.
+pub fn run_worker_with_args(args: &[&str], input: &Value) -> (bool, Value) {
+    run_worker_with_env(args, &[], input)
+}
+
+/// Like `run_worker_with_args`, with extra environment variables set.
+pub fn run_worker_with_env(args: &[&str], env: &[(&str, &OsStr)], input: &Value) -> (bool, Value) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust_worker"))
+        .args(args)
+        .envs(env.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn worker");
+    // The worker may exit without reading stdin, so a broken pipe is fine here
+    let _ = child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.to_string().as_bytes());
+    let output = child.wait_with_output().unwrap();
+    let reply = serde_json::from_slice(&output.stdout).expect("worker output is not JSON");
+    (output.status.success(), reply)
+}
+
+/// Run the worker binary with `input` on stdin and parse its JSON reply.
+pub fn run_worker(input: &Value) -> (bool, Value) {
+    run_worker_with_args(&[], input)
+}
+
+pub fn write_png(path: &Path) {
+    image::RgbaImage::new(4, 4).save(path).unwrap();
+}
@@ -0,0 +1,56 @@
+use base64::Engine;
+use image::{DynamicImage, ImageFormat};
+use rust_worker::process_image_bytes;
+use serde_json::{json, Value};
+use std::io::Cursor;
+
+fn png_data_url() -> String {
+    let mut png = Vec::new();
+    DynamicImage::ImageRgba8(image::RgbaImage::new(8, 6))
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .unwrap();
+    format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(&png))
+}
+
+fn call(request: &Value) -> Value {
+    serde_json::from_str(&process_image_bytes(&request.to_string())).unwrap()
+}
+
+#[test]
+fn returns_encoded_output() {
+    let reply = call(&json!({ "data_url": png_data_url(), "resize": [4, 3], "out_format": "jpeg" }));
+
+    assert_eq!(reply["ok"], true, "{}", reply);
+    assert_eq!(reply["out_path"], "");
+    assert!(reply.get("all_out_paths").is_none());
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(reply["output_bytes_b64"].as_str().unwrap())
+        .unwrap();
+    assert_eq!(image::guess_format(&bytes).unwrap(), ImageFormat::Jpeg);
+    assert_eq!(image::load_from_memory(&bytes).unwrap().width(), 4);
+}
+
+#[test]
+fn rejects_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("in.png");
+    image::RgbaImage::new(4, 4).save(&file).unwrap();
+
+    for request in [
+        json!({ "path": file }),
+        json!({ "data_url": png_data_url(), "out_path": dir.path().join("out.png") }),
+        json!({ "data_url": png_data_url(), "extra_outputs": [{ "out_path": dir.path().join("extra.png") }] }),
+    ] {
+        let reply = call(&request);
+        assert_eq!(reply["ok"], false);
+        assert_eq!(reply["error_code"], "INVALID_REQUEST", "{}", reply);
+    }
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn reports_invalid_json() {
+    let reply: Value = serde_json::from_str(&process_image_bytes("{ not json")).unwrap();
+    assert_eq!(reply["ok"], false);
+    assert!(reply["msg"].as_str().unwrap().starts_with("Invalid request JSON"));
+}
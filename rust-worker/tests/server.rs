@@ -0,0 +1,232 @@
+mod common;
+
+use common::write_png;
+use serde_json::{json, Value};
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, ChildStderr, Command, Stdio};
+use tempfile::tempdir;
+
+/// Worker running in `--serve` mode on an ephemeral port; killed on drop.
+struct ServerProcess {
+    child: Child,
+    addr: String,
+    /// Kept open so later writes to stderr don't hit a closed pipe
+    _stderr: BufReader<ChildStderr>,
+}
+
+impl ServerProcess {
+    fn start() -> Self {
+        Self::start_with_env(&[])
+    }
+
+    fn start_with_env(env: &[(&str, &OsStr)]) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_rust_worker"))
+            .args(["--serve", "127.0.0.1:0"])
+            .envs(env.iter().copied())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("failed to spawn worker");
+
+        // The first stderr line announces the bound address
+        let mut stderr = BufReader::new(child.stderr.take().unwrap());
+        let mut line = String::new();
+        stderr.read_line(&mut line).unwrap();
+        let addr = line
+            .trim()
+            .strip_prefix("Listening on http://")
+            .unwrap_or_else(|| panic!("unexpected banner: {:?}", line))
+            .to_string();
+        ServerProcess { child, addr, _stderr: stderr }
+    }
+
+    /// Send a raw HTTP/1.1 request and return the status code and JSON body.
+    fn request(&self, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let (status, body) = self.send(method, path, body);
+        (status, serde_json::from_str(&body).unwrap())
+    }
+
+    /// Send a bodyless request and return the plain-text body.
+    fn request_text(&self, method: &str, path: &str) -> String {
+        self.send(method, path, "").1
+    }
+
+    fn send(&self, method: &str, path: &str, body: &str) -> (u16, String) {
+        send(&self.addr, method, path, body)
+    }
+}
+
+/// Send a raw HTTP/1.1 request to `addr` and return the status code and body.
+fn send(addr: &str, method: &str, path: &str, body: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        addr,
+        body.len(),
+        body
+    )
+    .unwrap();
+
+    let mut raw = String::new();
+    stream.read_to_string(&mut raw).unwrap();
+    let (head, body) = raw.split_once("\r\n\r\n").expect("malformed HTTP response");
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, body.to_string())
+}
+
+impl Drop for ServerProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn serves_multiple_requests() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.png");
+    write_png(&input);
+    let server = ServerProcess::start();
+
+    for i in 0..3 {
+        let out = dir.path().join(format!("out{}.png", i));
+        let body = json!({ "path": input, "out_path": out, "resize": [2, 2] }).to_string();
+        let (status, reply) = server.request("POST", "/", &body);
+        assert_eq!(status, 200);
+        assert_eq!(reply["ok"], true);
+        assert_eq!(reply["width"], 2);
+        assert!(out.exists());
+    }
+}
+
+#[test]
+fn reports_errors_as_json() {
+    let dir = tempdir().unwrap();
+    let server = ServerProcess::start();
+
+    let (status, reply) = server.request("POST", "/", "not json");
+    assert_eq!(status, 400);
+    assert_eq!(reply["ok"], false);
+
+    let body = json!({ "path": dir.path().join("missing.png") }).to_string();
+    let (status, reply) = server.request("POST", "/", &body);
+    assert_eq!(status, 500);
+    assert_eq!(reply["ok"], false);
+
+    let (status, _) = server.request("GET", "/", "");
+    assert_eq!(status, 405);
+}
+
+#[test]
+fn panicking_request_gets_json_reply() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.png");
+    write_png(&input);
+    let server = ServerProcess::start_with_env(&[("RUST_WORKER_PANIC_PATH", input.as_os_str())]);
+
+    let body = json!({ "path": input, "out_path": dir.path().join("out.png") }).to_string();
+    let (status, reply) = server.request("POST", "/", &body);
+    assert_eq!(status, 500);
+    assert_eq!(reply["ok"], false);
+    assert_eq!(reply["error_code"], "UNKNOWN");
+    assert!(reply["msg"].as_str().unwrap().contains("Worker panicked"), "{}", reply);
+
+    // The server carries on with the next request
+    let (status, reply) = server.request("POST", "/", &json!({ "noop": true }).to_string());
+    assert_eq!(status, 200);
+    assert_eq!(reply["ok"], true);
+}
+
+#[test]
+fn health_endpoint_answers_ok() {
+    let server = ServerProcess::start();
+
+    let (status, reply) = server.request("GET", "/health", "");
+    assert_eq!(status, 200);
+    assert_eq!(reply["ok"], true);
+    assert_eq!(reply["msg"], "ok");
+
+    // A noop can also be posted like any other request
+    let (status, reply) = server.request("POST", "/", &json!({ "noop": true }).to_string());
+    assert_eq!(status, 200);
+    assert_eq!(reply["msg"], "ok");
+}
+
+#[test]
+fn metrics_count_processed_requests() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.png");
+    write_png(&input);
+    let server = ServerProcess::start();
+
+    for i in 0..4 {
+        let body = json!({ "path": input, "out_path": dir.path().join(format!("out{}.png", i)) }).to_string();
+        assert_eq!(server.request("POST", "/", &body).0, 200);
+    }
+    let body = json!({ "path": dir.path().join("missing.png") }).to_string();
+    assert_eq!(server.request("POST", "/", &body).0, 500);
+    // Health checks are not counted
+    server.request("GET", "/health", "");
+
+    let text = server.request_text("GET", "/metrics");
+    let count = |status: &str| -> u64 {
+        let prefix = format!("worker_requests_total{{status=\"{}\"}} ", status);
+        text.lines()
+            .find_map(|line| line.strip_prefix(&prefix))
+            .unwrap_or_else(|| panic!("no {} counter in:\n{}", status, text))
+            .parse()
+            .unwrap()
+    };
+    assert_eq!(count("ok") + count("error"), 5);
+    assert_eq!(count("error"), 1);
+    assert!(text.contains("worker_processing_duration_seconds_count 5"), "{}", text);
+}
+
+#[cfg(unix)]
+#[test]
+fn sigterm_drains_in_flight_request() {
+    use std::net::TcpListener;
+    use std::thread;
+
+    let dir = tempdir().unwrap();
+    let mut png = Vec::new();
+    image::RgbaImage::new(4, 4)
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageOutputFormat::Png)
+        .unwrap();
+    // The in-flight request stalls on this download until the test answers it
+    let origin = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/in.png", origin.local_addr().unwrap());
+    let mut server = ServerProcess::start();
+
+    let body = json!({ "fetch_url": url, "out_path": dir.path().join("out.png") }).to_string();
+    let addr = server.addr.clone();
+    let in_flight = thread::spawn(move || send(&addr, "POST", "/", &body));
+    let (mut download, _) = origin.accept().unwrap();
+
+    let status = Command::new("kill")
+        .args(["-TERM", &server.child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // Queued until the server notices the signal, then turned away
+    let (status, reply) = server.request("POST", "/", &json!({ "noop": true }).to_string());
+    assert_eq!(status, 503);
+    assert_eq!(reply["msg"], "server shutting down");
+
+    let mut head = [0u8; 1024];
+    let _ = download.read(&mut head).unwrap();
+    write!(download, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", png.len()).unwrap();
+    download.write_all(&png).unwrap();
+    drop(download);
+
+    let (status, reply) = in_flight.join().unwrap();
+    assert_eq!(status, 200, "{}", reply);
+    assert_eq!(serde_json::from_str::<Value>(&reply).unwrap()["ok"], true);
+    assert!(dir.path().join("out.png").exists());
+    assert!(server.child.wait().unwrap().success());
+}
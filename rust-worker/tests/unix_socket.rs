@@ -0,0 +1,122 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::write_png;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStderr, Command, Stdio};
+use tempfile::tempdir;
+
+/// Worker running in `--unix-socket` mode; killed on drop if still running.
+struct SocketServer {
+    child: Child,
+    path: PathBuf,
+    /// Kept open so later writes to stderr don't hit a closed pipe
+    _stderr: BufReader<ChildStderr>,
+}
+
+impl SocketServer {
+    fn start(path: &Path) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_rust_worker"))
+            .arg("--unix-socket")
+            .arg(path)
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("failed to spawn worker");
+
+        // Wait for the banner so the socket is known to be bound
+        let mut stderr = BufReader::new(child.stderr.take().unwrap());
+        let mut line = String::new();
+        stderr.read_line(&mut line).unwrap();
+        assert!(line.starts_with("Listening on unix:"), "unexpected banner: {:?}", line);
+        SocketServer { child, path: path.to_path_buf(), _stderr: stderr }
+    }
+
+    fn connect(&self) -> UnixStream {
+        UnixStream::connect(&self.path).unwrap()
+    }
+}
+
+impl Drop for SocketServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn send_frame(stream: &mut UnixStream, payload: &[u8]) {
+    stream.write_all(&(payload.len() as u32).to_be_bytes()).unwrap();
+    stream.write_all(payload).unwrap();
+}
+
+fn read_frame(stream: &mut UnixStream) -> Value {
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).unwrap();
+    let mut body = vec![0; u32::from_be_bytes(header) as usize];
+    stream.read_exact(&mut body).unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[test]
+fn answers_frames_on_one_connection() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.png");
+    write_png(&input);
+    let server = SocketServer::start(&dir.path().join("worker.sock"));
+
+    let mut stream = server.connect();
+    for name in ["a.png", "b.png"] {
+        let out = dir.path().join(name);
+        send_frame(&mut stream, json!({ "path": input, "out_path": out }).to_string().as_bytes());
+        let reply = read_frame(&mut stream);
+        assert_eq!(reply["ok"], true, "{}", reply);
+        assert!(out.exists());
+    }
+
+    send_frame(&mut stream, b"not json");
+    let reply = read_frame(&mut stream);
+    assert_eq!(reply["ok"], false);
+    assert!(reply["msg"].as_str().unwrap().starts_with("Invalid request"));
+}
+
+#[test]
+fn exits_cleanly_on_sigterm() {
+    let dir = tempdir().unwrap();
+    let socket = dir.path().join("worker.sock");
+    let mut server = SocketServer::start(&socket);
+
+    let status = Command::new("kill")
+        .args(["-TERM", &server.child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    assert!(server.child.wait().unwrap().success());
+    assert!(!socket.exists());
+}
+
+#[test]
+fn sigterm_closes_an_idle_connection() {
+    let dir = tempdir().unwrap();
+    let socket = dir.path().join("worker.sock");
+    let mut server = SocketServer::start(&socket);
+
+    // A client that stays connected without sending must not block shutdown
+    let mut stream = server.connect();
+    send_frame(&mut stream, json!({ "noop": true }).to_string().as_bytes());
+    assert_eq!(read_frame(&mut stream)["ok"], true);
+
+    let status = Command::new("kill")
+        .args(["-TERM", &server.child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    assert!(server.child.wait().unwrap().success());
+    assert!(!socket.exists());
+    let mut rest = Vec::new();
+    assert_eq!(stream.read_to_end(&mut rest).unwrap(), 0);
+}